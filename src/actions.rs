@@ -0,0 +1,173 @@
+use clap::ValueEnum;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+#[cfg(unix)]
+use std::os::unix::fs::symlink;
+#[cfg(windows)]
+use std::os::windows::fs::symlink_file as symlink;
+
+/// What to do with a confirmed duplicate once its keeper (the path that
+/// sorts first in its group, not the first copy the scan reached) has been
+/// decided.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Action {
+    /// Just print `dupe = keeper`, the original behavior.
+    Report,
+    /// Delete the duplicate, keeping only the keeper.
+    Delete,
+    /// Replace the duplicate with a hard link to the keeper.
+    Hardlink,
+    /// Replace the duplicate with a symbolic link to the keeper.
+    Symlink,
+}
+
+/// Applies `action` to a single `dupe` relative to its `keeper`. In
+/// `dry_run` mode this only prints what would happen.
+pub fn apply(action: Action, keeper: &Path, dupe: &Path, dry_run: bool) -> io::Result<()> {
+    match action {
+        // Reporting is handled by the `output` module; leave the duplicate alone.
+        Action::Report => Ok(()),
+        Action::Delete => {
+            if dry_run {
+                println!("would delete {}", dupe.display());
+                return Ok(());
+            }
+            fs::remove_file(dupe)
+        }
+        Action::Hardlink => replace_with(keeper, dupe, dry_run, "hardlink", |tmp| {
+            fs::hard_link(keeper, tmp)
+        }),
+        Action::Symlink => {
+            // A symlink's target text is resolved relative to the symlink's
+            // own parent directory, not the process's CWD, so we can't just
+            // point it at `keeper`'s scan-relative path whenever the two
+            // files live in different directories.
+            let target = symlink_target(keeper, dupe)?;
+            replace_with(keeper, dupe, dry_run, "symlink", |tmp| symlink(&target, tmp))
+        }
+    }
+}
+
+/// Computes the path to write as a symlink's target: `keeper`, expressed
+/// relative to `dupe`'s parent directory.
+fn symlink_target(keeper: &Path, dupe: &Path) -> io::Result<PathBuf> {
+    let keeper_abs = fs::canonicalize(keeper)?;
+    let dupe_dir = match dupe.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let dupe_dir_abs = fs::canonicalize(dupe_dir)?;
+    Ok(relative_path(&dupe_dir_abs, &keeper_abs))
+}
+
+/// Computes the relative path from directory `base` to `target`.
+fn relative_path(base: &Path, target: &Path) -> PathBuf {
+    let base_components: Vec<_> = base.components().collect();
+    let target_components: Vec<_> = target.components().collect();
+
+    let common = base_components
+        .iter()
+        .zip(target_components.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut result = PathBuf::new();
+    for _ in common..base_components.len() {
+        result.push("..");
+    }
+    for component in &target_components[common..] {
+        result.push(component.as_os_str());
+    }
+    result
+}
+
+/// Creates a link at a temporary sibling path and atomically renames it over
+/// `dupe`, so a crash mid-operation never leaves `dupe` missing.
+fn replace_with(
+    keeper: &Path,
+    dupe: &Path,
+    dry_run: bool,
+    verb: &str,
+    make_link: impl FnOnce(&Path) -> io::Result<()>,
+) -> io::Result<()> {
+    if dry_run {
+        println!("would {verb} {} -> {}", dupe.display(), keeper.display());
+        return Ok(());
+    }
+    let tmp = tmp_path_for(dupe);
+    make_link(&tmp)?;
+    fs::rename(&tmp, dupe)
+}
+
+fn tmp_path_for(path: &Path) -> PathBuf {
+    let mut name = path.file_name().expect("dupe path has no file name").to_owned();
+    name.push(".dupes-tmp");
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_support::scratch_dir;
+
+    /// `d1/a.txt` (the keeper) and `d2/b.txt` (the dupe) under a fresh
+    /// scratch directory, so every test exercises two different parent
+    /// directories the way a real dedup run would.
+    fn two_dirs(label: &str, contents: &[u8]) -> (PathBuf, PathBuf) {
+        let base = scratch_dir("actions", label);
+        let d1 = base.join("d1");
+        let d2 = base.join("d2");
+        fs::create_dir_all(&d1).unwrap();
+        fs::create_dir_all(&d2).unwrap();
+        let keeper = d1.join("a.txt");
+        let dupe = d2.join("b.txt");
+        fs::write(&keeper, contents).unwrap();
+        fs::write(&dupe, contents).unwrap();
+        (keeper, dupe)
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn symlink_across_directories_resolves_to_keeper_contents() {
+        let (keeper, dupe) = two_dirs("symlink", b"dup-test");
+        apply(Action::Symlink, &keeper, &dupe, false).unwrap();
+
+        assert!(dupe.is_symlink());
+        assert_eq!(fs::read(&dupe).unwrap(), b"dup-test");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn hardlink_across_directories_shares_the_keeper_inode() {
+        use std::os::unix::fs::MetadataExt;
+
+        let (keeper, dupe) = two_dirs("hardlink", b"dup-test");
+        apply(Action::Hardlink, &keeper, &dupe, false).unwrap();
+
+        assert_eq!(
+            fs::metadata(&dupe).unwrap().ino(),
+            fs::metadata(&keeper).unwrap().ino()
+        );
+    }
+
+    #[test]
+    fn delete_removes_only_the_dupe() {
+        let (keeper, dupe) = two_dirs("delete", b"dup-test");
+        apply(Action::Delete, &keeper, &dupe, false).unwrap();
+
+        assert!(!dupe.exists());
+        assert!(keeper.exists());
+    }
+
+    #[test]
+    fn dry_run_touches_nothing() {
+        let (keeper, dupe) = two_dirs("dry-run", b"dup-test");
+        apply(Action::Delete, &keeper, &dupe, true).unwrap();
+
+        assert!(dupe.exists());
+        assert!(keeper.exists());
+    }
+}
@@ -0,0 +1,120 @@
+//! A BK-tree over 64-bit perceptual hashes, giving sub-linear
+//! nearest-neighbor search under the Hamming metric.
+use crate::phash::hamming_distance;
+use std::{collections::HashMap, path::PathBuf};
+
+struct Node {
+    hash: u64,
+    paths: Vec<PathBuf>,
+    children: HashMap<u32, Node>,
+}
+
+#[derive(Default)]
+pub struct BkTree {
+    root: Option<Node>,
+}
+
+impl BkTree {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, hash: u64, path: PathBuf) {
+        match &mut self.root {
+            None => {
+                self.root = Some(Node {
+                    hash,
+                    paths: vec![path],
+                    children: HashMap::new(),
+                });
+            }
+            Some(root) => Self::insert_into(root, hash, path),
+        }
+    }
+
+    fn insert_into(node: &mut Node, hash: u64, path: PathBuf) {
+        if node.hash == hash {
+            node.paths.push(path);
+            return;
+        }
+        let distance = hamming_distance(node.hash, hash);
+        match node.children.get_mut(&distance) {
+            Some(child) => Self::insert_into(child, hash, path),
+            None => {
+                node.children.insert(
+                    distance,
+                    Node {
+                        hash,
+                        paths: vec![path],
+                        children: HashMap::new(),
+                    },
+                );
+            }
+        }
+    }
+
+    /// Returns every inserted hash (and its paths) within `threshold` of
+    /// `hash`. Each visited node's child branches are keyed by their
+    /// distance to that node, so the triangle inequality lets us skip any
+    /// branch whose distance bound can't possibly satisfy the threshold.
+    pub fn query(&self, hash: u64, threshold: u32) -> Vec<(u64, &[PathBuf])> {
+        let mut results = Vec::new();
+        if let Some(root) = &self.root {
+            Self::query_node(root, hash, threshold, &mut results);
+        }
+        results
+    }
+
+    fn query_node<'a>(
+        node: &'a Node,
+        hash: u64,
+        threshold: u32,
+        results: &mut Vec<(u64, &'a [PathBuf])>,
+    ) {
+        let distance = hamming_distance(node.hash, hash);
+        if distance <= threshold {
+            results.push((node.hash, &node.paths));
+        }
+        let lo = distance.saturating_sub(threshold);
+        let hi = distance + threshold;
+        for (child_distance, child) in &node.children {
+            if *child_distance >= lo && *child_distance <= hi {
+                Self::query_node(child, hash, threshold, results);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn query_finds_near_neighbors_within_threshold() {
+        let mut tree = BkTree::new();
+        tree.insert(0b0000_0000, PathBuf::from("a"));
+        tree.insert(0b0000_0001, PathBuf::from("b")); // distance 1 from a
+        tree.insert(0b0000_0111, PathBuf::from("c")); // distance 3 from a
+        tree.insert(0xFFFF_FFFF_FFFF_FFFF, PathBuf::from("d")); // distance 64 from a
+
+        let mut found: Vec<PathBuf> = tree
+            .query(0b0000_0000, 2)
+            .into_iter()
+            .flat_map(|(_, paths)| paths.to_vec())
+            .collect();
+        found.sort();
+
+        assert_eq!(found, vec![PathBuf::from("a"), PathBuf::from("b")]);
+    }
+
+    #[test]
+    fn insert_groups_identical_hashes_into_one_node() {
+        let mut tree = BkTree::new();
+        tree.insert(42, PathBuf::from("a"));
+        tree.insert(42, PathBuf::from("b"));
+
+        let results = tree.query(42, 0);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].1, &[PathBuf::from("a"), PathBuf::from("b")]);
+    }
+}
@@ -0,0 +1,179 @@
+use crate::dupe_scanner::{HashAlgo, HashMode};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Identifies a cached hash: the file's absolute, canonicalized path plus the
+/// `(len, mtime)` pair used to detect whether the file changed since it was
+/// last hashed, and the algorithm/mode that produced the digest (different
+/// combinations produce different hashes for the same bytes). The path must
+/// be canonicalized so the same file scanned via two different relative
+/// prefixes (or from a different working directory) still hits the cache.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+struct CacheKey {
+    path: PathBuf,
+    len: u64,
+    mtime: i64,
+    algo: HashAlgo,
+    mode: HashMode,
+}
+
+/// Persistent, per-file hash cache so repeated scans of a mostly-static tree
+/// can skip re-reading files whose size and modification time haven't
+/// changed since they were last hashed.
+pub struct HashCache {
+    path: PathBuf,
+    entries: HashMap<CacheKey, Vec<u8>>,
+}
+
+impl HashCache {
+    /// Loads the cache from `path`, starting empty if it doesn't exist yet or
+    /// fails to parse.
+    pub fn load(path: PathBuf) -> Self {
+        let entries = fs::read(&path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<Vec<(CacheKey, Vec<u8>)>>(&bytes).ok())
+            .map(|pairs| pairs.into_iter().collect())
+            .unwrap_or_default();
+        HashCache { path, entries }
+    }
+
+    /// Default cache file location, under the user's cache directory.
+    pub fn default_path() -> PathBuf {
+        directories::ProjectDirs::from("", "", "dupes")
+            .map(|dirs| dirs.cache_dir().join("hashes.json"))
+            .unwrap_or_else(|| PathBuf::from(".dupes-cache.json"))
+    }
+
+    pub fn get(
+        &self,
+        path: &Path,
+        len: u64,
+        mtime: i64,
+        algo: HashAlgo,
+        mode: HashMode,
+    ) -> Option<Vec<u8>> {
+        let key = CacheKey {
+            path: fs::canonicalize(path).ok()?,
+            len,
+            mtime,
+            algo,
+            mode,
+        };
+        self.entries.get(&key).cloned()
+    }
+
+    pub fn insert(
+        &mut self,
+        path: &Path,
+        len: u64,
+        mtime: i64,
+        algo: HashAlgo,
+        mode: HashMode,
+        hash: Vec<u8>,
+    ) {
+        let Ok(path) = fs::canonicalize(path) else {
+            return;
+        };
+        let key = CacheKey {
+            path,
+            len,
+            mtime,
+            algo,
+            mode,
+        };
+        self.entries.insert(key, hash);
+    }
+
+    /// Writes the cache back to disk, dropping entries for files that no
+    /// longer exist.
+    pub fn save(self) -> io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let pairs: Vec<(CacheKey, Vec<u8>)> = self
+            .entries
+            .into_iter()
+            .filter(|(key, _)| key.path.exists())
+            .collect();
+        let bytes = serde_json::to_vec(&pairs)?;
+        fs::write(&self.path, bytes)
+    }
+}
+
+/// A file's `(len, mtime)` stamp, used as the change-detection half of a
+/// `CacheKey`. `mtime` is seconds since the epoch.
+pub fn file_stamp(path: &Path) -> io::Result<(u64, i64)> {
+    let metadata = fs::metadata(path)?;
+    let mtime = metadata
+        .modified()?
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    Ok((metadata.len(), mtime))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_support::scratch_dir;
+
+    #[test]
+    fn hit_survives_a_save_and_reload() {
+        let dir = scratch_dir("cache", "roundtrip");
+        let file = dir.join("a.txt");
+        fs::write(&file, b"hello").unwrap();
+        let (len, mtime) = file_stamp(&file).unwrap();
+        let cache_path = dir.join("cache.json");
+
+        let mut cache = HashCache::load(cache_path.clone());
+        assert!(cache
+            .get(&file, len, mtime, HashAlgo::Sha1, HashMode::Full)
+            .is_none());
+        cache.insert(&file, len, mtime, HashAlgo::Sha1, HashMode::Full, vec![1, 2, 3]);
+        cache.save().unwrap();
+
+        let reloaded = HashCache::load(cache_path);
+        assert_eq!(
+            reloaded.get(&file, len, mtime, HashAlgo::Sha1, HashMode::Full),
+            Some(vec![1, 2, 3])
+        );
+    }
+
+    #[test]
+    fn hit_survives_a_different_relative_prefix() {
+        let dir = scratch_dir("cache", "relative-prefix");
+        let file = dir.join("b.txt");
+        fs::write(&file, b"world").unwrap();
+        let (len, mtime) = file_stamp(&file).unwrap();
+
+        let mut cache = HashCache::load(dir.join("cache.json"));
+        cache.insert(&file, len, mtime, HashAlgo::Blake3, HashMode::Full, vec![9]);
+
+        let relative = dir.join(".").join("b.txt");
+        assert_eq!(
+            cache.get(&relative, len, mtime, HashAlgo::Blake3, HashMode::Full),
+            Some(vec![9])
+        );
+    }
+
+    #[test]
+    fn save_prunes_entries_for_deleted_files() {
+        let dir = scratch_dir("cache", "prune");
+        let file = dir.join("c.txt");
+        fs::write(&file, b"!").unwrap();
+        let (len, mtime) = file_stamp(&file).unwrap();
+        let cache_path = dir.join("cache.json");
+
+        let mut cache = HashCache::load(cache_path.clone());
+        cache.insert(&file, len, mtime, HashAlgo::Crc32, HashMode::Full, vec![7]);
+        fs::remove_file(&file).unwrap();
+        cache.save().unwrap();
+
+        let reloaded = HashCache::load(cache_path);
+        assert!(reloaded.entries.is_empty());
+    }
+}
@@ -1,18 +1,58 @@
+use crate::actions::{self, Action};
+use crate::cache::{file_stamp, HashCache};
+use crate::filters::ScanFilters;
+use crate::output::{self, OutputFormat};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
 use sha1::{Digest, Sha1};
 use std::{
     collections::HashMap,
     io::{BufReader, Read},
-    path::Path,
-    sync::{Arc, RwLock},
+    path::{Path, PathBuf},
+    sync::{mpsc, Arc, Mutex, RwLock},
 };
 use threadpool::ThreadPool;
 
+const BUFFER_SIZE: usize = 4096;
+
+/// Hashing algorithm used to fingerprint file contents.
+///
+/// `Blake3` is the default: it parallelizes internally and is fast even on
+/// large files. `Xxh3` and `Crc32` are non-cryptographic and faster still,
+/// which is fine here since we only need collision resistance against
+/// accidental matches, not an adversary. `Sha1` is kept for anyone who wants
+/// the previous behavior.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum, Serialize, Deserialize)]
+pub enum HashAlgo {
+    Sha1,
+    Blake3,
+    Xxh3,
+    Crc32,
+}
+
 type SeenFiles = Arc<RwLock<HashMap<Vec<u8>, String>>>;
+
+/// How much of a file a call to `hash_file` should read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub(crate) enum HashMode {
+    /// Hash only the first `BUFFER_SIZE` bytes. Cheap enough to run over an
+    /// entire size-collision bucket before committing to a full read.
+    Partial,
+    /// Hash the whole file. The only mode that can actually confirm a duplicate.
+    Full,
+}
+
 pub struct DupeScanner {
     start_dir: String,
     worker_pool: ThreadPool,
     seen_files: SeenFiles,
     ignore_symlinks: bool,
+    hash_algo: HashAlgo,
+    cache: Option<Arc<Mutex<HashCache>>>,
+    action: Action,
+    dry_run: bool,
+    output_format: OutputFormat,
+    filters: ScanFilters,
 }
 
 impl Default for DupeScanner {
@@ -22,52 +62,163 @@ impl Default for DupeScanner {
             worker_pool: Default::default(),
             seen_files: Default::default(),
             ignore_symlinks: true,
+            hash_algo: HashAlgo::Blake3,
+            cache: None,
+            action: Action::Report,
+            dry_run: false,
+            output_format: OutputFormat::Text,
+            filters: Default::default(),
         }
     }
 }
 
 impl DupeScanner {
-    pub fn new(start_directory: &str, worker_pool_size: usize, ignore_symlinks: bool) -> Self {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        start_directory: &str,
+        worker_pool_size: usize,
+        ignore_symlinks: bool,
+        hash_algo: HashAlgo,
+        cache_path: Option<PathBuf>,
+        action: Action,
+        dry_run: bool,
+        output_format: OutputFormat,
+        filters: ScanFilters,
+    ) -> Self {
         DupeScanner {
             start_dir: String::from(start_directory),
             worker_pool: ThreadPool::new(worker_pool_size),
             seen_files: Arc::new(RwLock::new(HashMap::new())),
             ignore_symlinks,
+            hash_algo,
+            cache: cache_path.map(|p| Arc::new(Mutex::new(HashCache::load(p)))),
+            action,
+            dry_run,
+            output_format,
+            filters,
         }
     }
 
-    pub fn from_path(path: &Path, ignore_symlinks: bool) -> Self {
+    #[allow(clippy::too_many_arguments)]
+    pub fn from_path(
+        path: &Path,
+        ignore_symlinks: bool,
+        hash_algo: HashAlgo,
+        cache_path: Option<PathBuf>,
+        action: Action,
+        dry_run: bool,
+        output_format: OutputFormat,
+        filters: ScanFilters,
+    ) -> Self {
         DupeScanner {
             start_dir: String::from(path.to_str().unwrap()),
             worker_pool: Default::default(),
             seen_files: Default::default(),
             ignore_symlinks,
+            hash_algo,
+            cache: cache_path.map(|p| Arc::new(Mutex::new(HashCache::load(p)))),
+            action,
+            dry_run,
+            output_format,
+            filters,
         }
     }
 
+    /// Three-pass pipeline: bucket by size, then by a cheap partial hash, and
+    /// only compute a full-file hash for files that collided on both. Most
+    /// files in a tree have a unique size and are discarded after the first
+    /// pass without ever being read.
     pub fn find_dupes(&mut self) -> std::io::Result<()> {
         let start_dir = Path::new(&self.start_dir).to_owned();
-        self.scan_directory(&start_dir)?;
-        self.worker_pool.join();
+
+        let mut by_size: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+        self.collect_by_size(&start_dir, &mut by_size)?;
+        let size_candidates = Self::surviving_paths(by_size);
+
+        let by_partial_hash = self.group_by_hash(size_candidates, HashMode::Partial);
+        // A partial-hash group can mix files of different sizes, so split it
+        // by size again: only files matching on both go on to a full hash.
+        let mut partial_candidates = Vec::new();
+        for paths in by_partial_hash.into_values() {
+            let mut by_len: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+            for path in paths {
+                by_len.entry(std::fs::metadata(&path)?.len()).or_default().push(path);
+            }
+            partial_candidates.extend(Self::surviving_paths(by_len));
+        }
+
+        let by_full_hash = self.group_by_hash(partial_candidates, HashMode::Full);
+
+        let mut seen_files = self.seen_files.write().unwrap();
+        let mut groups = Vec::new();
+        for (hash, mut paths) in by_full_hash {
+            if paths.len() < 2 {
+                continue;
+            }
+            // `paths` arrives in whatever order the worker-pool's mpsc
+            // channel happened to deliver results, which varies run to run.
+            // Sort so the same tree always picks the same keeper, since
+            // that choice is irreversible for `Delete`/`Hardlink`/`Symlink`.
+            paths.sort();
+            let keeper = &paths[0];
+            for dup in &paths[1..] {
+                actions::apply(self.action, keeper, dup, self.dry_run)?;
+            }
+            seen_files.insert(hash.clone(), String::from(keeper.to_str().unwrap()));
+            groups.push((hash, paths));
+        }
+        drop(seen_files);
+
+        output::report(groups, self.output_format)?;
+
+        if let Some(cache) = self.cache.take() {
+            // Every worker's `Arc<Mutex<HashCache>>` clone is dropped as soon
+            // as its closure returns, but `tx.send` (the only thing
+            // `group_by_hash` waits on) happens *before* that drop, so
+            // without this join we could race a worker still holding its
+            // clone and silently fail to persist the cache.
+            self.worker_pool.join();
+            let cache = Arc::try_unwrap(cache).unwrap_or_else(|_| {
+                panic!("cache still has outstanding references after worker_pool.join()")
+            });
+            cache.into_inner().unwrap().save()?;
+        }
+
         Ok(())
     }
 
-    fn scan_directory(&mut self, path: &Path) -> std::io::Result<()> {
+    /// Flattens every bucket with more than one entry; singleton buckets
+    /// cannot contain a duplicate and are dropped.
+    fn surviving_paths<K>(buckets: HashMap<K, Vec<PathBuf>>) -> Vec<PathBuf> {
+        buckets
+            .into_values()
+            .filter(|paths| paths.len() > 1)
+            .flatten()
+            .collect()
+    }
+
+    fn collect_by_size(
+        &self,
+        path: &Path,
+        out: &mut HashMap<u64, Vec<PathBuf>>,
+    ) -> std::io::Result<()> {
         if path.is_dir() {
             for entry in std::fs::read_dir(path)? {
-                let entry = entry?;
-                let path = entry.path();
-                if !path.is_dir() {
-                    let seen_copy = self.seen_files.clone();
-                    // Ignore symlinks if needed
-                    if self.ignore_symlinks && path.is_symlink() {
-                        continue;
-                    }
-                    self.worker_pool.execute(move || {
-                        handle_file(seen_copy, path.as_path());
-                    });
+                let entry_path = entry?.path();
+                // Ignore symlinks if needed
+                if self.ignore_symlinks && entry_path.is_symlink() {
+                    continue;
+                }
+                if self.filters.excludes_path(&entry_path) {
+                    continue;
+                }
+                if entry_path.is_dir() {
+                    self.collect_by_size(&entry_path, out)?;
                 } else {
-                    self.scan_directory(&entry.path())?;
+                    let len = std::fs::metadata(&entry_path)?.len();
+                    if self.filters.accepts_file(&entry_path, len) {
+                        out.entry(len).or_default().push(entry_path);
+                    }
                 }
             }
         } else {
@@ -75,40 +226,109 @@ impl DupeScanner {
             if self.ignore_symlinks && path.is_symlink() {
                 return Ok(());
             }
-            let copy = self.seen_files.clone();
-            let path_copy = path.to_owned();
+            if self.filters.excludes_path(path) {
+                return Ok(());
+            }
+            let len = std::fs::metadata(path)?.len();
+            if self.filters.accepts_file(path, len) {
+                out.entry(len).or_default().push(path.to_owned());
+            }
+        }
+        Ok(())
+    }
+
+    /// Hashes `paths` across the worker pool using `mode` and groups the
+    /// results by the resulting digest.
+    fn group_by_hash(&self, paths: Vec<PathBuf>, mode: HashMode) -> HashMap<Vec<u8>, Vec<PathBuf>> {
+        let total = paths.len();
+        let (tx, rx) = mpsc::channel();
+        let algo = self.hash_algo;
+        for path in paths {
+            let tx = tx.clone();
+            let cache = self.cache.clone();
             self.worker_pool.execute(move || {
-                handle_file(copy, &path_copy);
+                let hash = hash_file_cached(&path, mode, algo, cache.as_deref());
+                tx.send((hash, path)).expect("receiver dropped");
             });
         }
-        Ok(())
+        drop(tx);
+
+        let mut grouped: HashMap<Vec<u8>, Vec<PathBuf>> = HashMap::new();
+        for (hash, path) in rx.iter().take(total) {
+            grouped.entry(hash).or_default().push(path);
+        }
+        grouped
     }
 }
 
-fn handle_file(seen_files: SeenFiles, path: &Path) {
-    let hash = hash_file(path);
-    let seen_file;
-    {
-        if let Some(s) = seen_files.read().unwrap().get(&hash) {
-            seen_file = Some(s.clone());
-        } else {
-            seen_file = None;
+/// Incremental hasher covering all of `HashAlgo`'s variants behind one type
+/// so `hash_file` can stay agnostic to which one was picked.
+enum Hasher {
+    Sha1(Sha1),
+    Blake3(blake3::Hasher),
+    Xxh3(xxhash_rust::xxh3::Xxh3),
+    Crc32(crc32fast::Hasher),
+}
+
+impl Hasher {
+    fn new(algo: HashAlgo) -> Self {
+        match algo {
+            HashAlgo::Sha1 => Hasher::Sha1(Sha1::new()),
+            HashAlgo::Blake3 => Hasher::Blake3(blake3::Hasher::new()),
+            HashAlgo::Xxh3 => Hasher::Xxh3(xxhash_rust::xxh3::Xxh3::new()),
+            HashAlgo::Crc32 => Hasher::Crc32(crc32fast::Hasher::new()),
         }
     }
 
-    if let Some(x) = seen_file {
-        println!("{} = {}", path.to_str().unwrap(), x);
-    } else {
-        seen_files
-            .write()
-            .unwrap()
-            .insert(hash, String::from(path.to_str().unwrap()));
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Sha1(h) => h.update(data),
+            Hasher::Blake3(h) => {
+                h.update(data);
+            }
+            Hasher::Xxh3(h) => h.update(data),
+            Hasher::Crc32(h) => h.update(data),
+        }
     }
+
+    fn finalize(self) -> Vec<u8> {
+        match self {
+            Hasher::Sha1(h) => h.finalize().to_vec(),
+            Hasher::Blake3(h) => h.finalize().as_bytes().to_vec(),
+            Hasher::Xxh3(h) => h.digest().to_be_bytes().to_vec(),
+            Hasher::Crc32(h) => h.finalize().to_be_bytes().to_vec(),
+        }
+    }
+}
+
+/// Looks up `path`'s hash in `cache` before falling back to actually reading
+/// the file, storing any freshly computed hash back into the cache.
+fn hash_file_cached(
+    path: &Path,
+    mode: HashMode,
+    algo: HashAlgo,
+    cache: Option<&Mutex<HashCache>>,
+) -> Vec<u8> {
+    let cache = match cache {
+        Some(cache) => cache,
+        None => return hash_file(path, mode, algo),
+    };
+    let Ok((len, mtime)) = file_stamp(path) else {
+        return hash_file(path, mode, algo);
+    };
+    if let Some(hash) = cache.lock().unwrap().get(path, len, mtime, algo, mode) {
+        return hash;
+    }
+    let hash = hash_file(path, mode, algo);
+    cache
+        .lock()
+        .unwrap()
+        .insert(path, len, mtime, algo, mode, hash.clone());
+    hash
 }
 
-fn hash_file(path: &Path) -> Vec<u8> {
-    const BUFFER_SIZE: usize = 4096;
-    let mut hasher = Sha1::new();
+fn hash_file(path: &Path, mode: HashMode, algo: HashAlgo) -> Vec<u8> {
+    let mut hasher = Hasher::new(algo);
     let file = std::fs::File::open(path).unwrap();
     let mut reader = BufReader::new(file);
     let mut buffer = [0u8; BUFFER_SIZE];
@@ -116,49 +336,105 @@ fn hash_file(path: &Path) -> Vec<u8> {
         if let Ok(bytes_read) = reader.read(&mut buffer) {
             if bytes_read == 0 {
                 break;
-            } else {
-                hasher.update(&buffer[..bytes_read]);
+            }
+            hasher.update(&buffer[..bytes_read]);
+            if mode == HashMode::Partial {
+                break;
             }
         }
     }
-    hasher.finalize().to_vec()
+    hasher.finalize()
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::test_support::scratch_dir;
     use hex_literal::hex;
+
     #[test]
     fn test_hasher_hello() {
-        let result = hash_file(Path::new("test/helloworld.txt"));
+        let result = hash_file(Path::new("test/helloworld.txt"), HashMode::Full, HashAlgo::Sha1);
         assert_eq!(result[..], hex!("2aae6c35c94fcfb415dbe95f408b9ce91ee846ed"));
     }
 
     #[test]
     fn test_hasher_odyssey() {
-        let result = hash_file(Path::new("test/odyssey.mb.txt"));
+        let result = hash_file(Path::new("test/odyssey.mb.txt"), HashMode::Full, HashAlgo::Sha1);
         assert_eq!(result[..], hex!("84d81cb70dfc52a964e3c6f38d753533e134a9e8"));
     }
+
+    /// Writes `contents` to a fresh scratch file and returns its path, so the
+    /// new-algorithm tests below don't depend on the checked-in fixtures
+    /// (which only have SHA-1 digests recorded above).
+    fn scratch_file(label: &str, contents: &[u8]) -> PathBuf {
+        let dir = scratch_dir("hasher", "fixtures");
+        let path = dir.join(label);
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_hasher_crc32_known_answer() {
+        // CRC-32 (IEEE) of the ASCII digits "123456789" is the standard
+        // check value quoted by every CRC-32 implementation's test suite.
+        let path = scratch_file("crc32-check.txt", b"123456789");
+        let result = hash_file(&path, HashMode::Full, HashAlgo::Crc32);
+        assert_eq!(result, 0xCBF43926u32.to_be_bytes().to_vec());
+    }
+
+    #[test]
+    fn identical_files_hash_equal_under_every_algo() {
+        for algo in [HashAlgo::Sha1, HashAlgo::Blake3, HashAlgo::Xxh3, HashAlgo::Crc32] {
+            let a = scratch_file(&format!("{algo:?}-a.txt"), b"same contents");
+            let b = scratch_file(&format!("{algo:?}-b.txt"), b"same contents");
+            let hash_a = hash_file(&a, HashMode::Full, algo);
+            let hash_b = hash_file(&b, HashMode::Full, algo);
+            assert_eq!(hash_a, hash_b, "{algo:?} gave different hashes for identical content");
+        }
+    }
+
+    #[test]
+    fn different_files_hash_differently_under_every_algo() {
+        for algo in [HashAlgo::Sha1, HashAlgo::Blake3, HashAlgo::Xxh3, HashAlgo::Crc32] {
+            let a = scratch_file(&format!("{algo:?}-diff-a.txt"), b"content one");
+            let b = scratch_file(&format!("{algo:?}-diff-b.txt"), b"content two");
+            let hash_a = hash_file(&a, HashMode::Full, algo);
+            let hash_b = hash_file(&b, HashMode::Full, algo);
+            assert_ne!(hash_a, hash_b, "{algo:?} collided on different content");
+        }
+    }
+
     #[test]
     fn test_dupes() {
+        // test1.txt has no duplicate and its unique size drops it in the
+        // first pass, so only the two duplicated contents survive.
         let mut correct = HashMap::new();
         correct.insert(
             hex!("2aae6c35c94fcfb415dbe95f408b9ce91ee846ed").to_vec(),
-            "test/test2.txt".to_string(),
+            "test/helloworld.txt".to_string(),
         );
         correct.insert(
             hex!("84d81cb70dfc52a964e3c6f38d753533e134a9e8").to_vec(),
-            "test/odyssey2.txt".to_string(),
-        );
-        correct.insert(
-            hex!("b444ac06613fc8d63795be9ad0beaf55011936ac").to_vec(),
-            "test/test1.txt".to_string(),
+            "test/odyssey.mb.txt".to_string(),
         );
 
-        let mut dupe_scanner = DupeScanner::new("test/", 8, true);
+        let mut dupe_scanner = DupeScanner::new(
+            "test/",
+            8,
+            true,
+            HashAlgo::Sha1,
+            None,
+            Action::Report,
+            false,
+            OutputFormat::Text,
+            ScanFilters::default(),
+        );
         dupe_scanner.find_dupes().unwrap();
-        for (hash, _) in correct {
-            assert!(dupe_scanner.seen_files.read().unwrap().get(&hash).is_some());
+        let seen = dupe_scanner.seen_files.read().unwrap();
+        assert_eq!(seen.len(), correct.len());
+        for hash in correct.keys() {
+            assert!(seen.get(hash).is_some());
         }
     }
 }
@@ -0,0 +1,114 @@
+use glob::Pattern;
+use std::path::Path;
+
+/// Include/exclude rules applied while walking the tree, evaluated before a
+/// file (or directory, for `exclude_path`) is ever queued for hashing.
+#[derive(Debug, Default, Clone)]
+pub struct ScanFilters {
+    include_ext: Vec<String>,
+    exclude_ext: Vec<String>,
+    exclude_path: Vec<Pattern>,
+    min_size: Option<u64>,
+    max_size: Option<u64>,
+}
+
+impl ScanFilters {
+    pub fn new(
+        include_ext: Vec<String>,
+        exclude_ext: Vec<String>,
+        exclude_path: Vec<String>,
+        min_size: Option<u64>,
+        max_size: Option<u64>,
+    ) -> Self {
+        ScanFilters {
+            include_ext: include_ext.into_iter().map(|e| e.to_lowercase()).collect(),
+            exclude_ext: exclude_ext.into_iter().map(|e| e.to_lowercase()).collect(),
+            exclude_path: exclude_path
+                .iter()
+                .filter_map(|pattern| Pattern::new(pattern).ok())
+                .collect(),
+            min_size,
+            max_size,
+        }
+    }
+
+    /// Whether `path` (file or directory) matches an `--exclude-path` glob
+    /// and should be pruned entirely, e.g. a `node_modules` directory.
+    pub fn excludes_path(&self, path: &Path) -> bool {
+        self.exclude_path
+            .iter()
+            .any(|pattern| pattern.matches_path(path))
+    }
+
+    /// Whether a regular file of size `len` passes the extension and size
+    /// filters.
+    pub fn accepts_file(&self, path: &Path, len: u64) -> bool {
+        if self.min_size.is_some_and(|min| len < min) || self.max_size.is_some_and(|max| len > max)
+        {
+            return false;
+        }
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase());
+        if let Some(ext) = &ext {
+            if self.exclude_ext.contains(ext) {
+                return false;
+            }
+        }
+        if !self.include_ext.is_empty() {
+            return ext.is_some_and(|ext| self.include_ext.contains(&ext));
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn accepts_everything_by_default() {
+        let filters = ScanFilters::default();
+        assert!(filters.accepts_file(Path::new("a/b.mp4"), 0));
+        assert!(!filters.excludes_path(Path::new("a/node_modules/b.mp4")));
+    }
+
+    #[test]
+    fn include_ext_is_case_insensitive_and_exclusive() {
+        let filters = ScanFilters::new(vec!["MP4".into()], vec![], vec![], None, None);
+        assert!(filters.accepts_file(Path::new("movie.mp4"), 0));
+        assert!(filters.accepts_file(Path::new("movie.MP4"), 0));
+        assert!(!filters.accepts_file(Path::new("movie.mov"), 0));
+        assert!(!filters.accepts_file(Path::new("no_extension"), 0));
+    }
+
+    #[test]
+    fn exclude_ext_rejects_matching_files() {
+        let filters = ScanFilters::new(vec![], vec!["tmp".into()], vec![], None, None);
+        assert!(!filters.accepts_file(Path::new("scratch.tmp"), 0));
+        assert!(filters.accepts_file(Path::new("scratch.txt"), 0));
+    }
+
+    #[test]
+    fn size_bounds_are_inclusive() {
+        let filters = ScanFilters::new(vec![], vec![], vec![], Some(10), Some(100));
+        assert!(!filters.accepts_file(Path::new("f"), 9));
+        assert!(filters.accepts_file(Path::new("f"), 10));
+        assert!(filters.accepts_file(Path::new("f"), 100));
+        assert!(!filters.accepts_file(Path::new("f"), 101));
+    }
+
+    #[test]
+    fn exclude_path_prunes_matching_globs() {
+        let filters = ScanFilters::new(
+            vec![],
+            vec![],
+            vec!["**/node_modules/**".into()],
+            None,
+            None,
+        );
+        assert!(filters.excludes_path(Path::new("project/node_modules/pkg")));
+        assert!(!filters.excludes_path(Path::new("project/src/pkg")));
+    }
+}
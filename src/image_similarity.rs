@@ -0,0 +1,64 @@
+//! Visually-similar image detection. A separate pipeline from the
+//! byte-hash duplicate scan: it clusters images by perceptual hash distance
+//! rather than requiring an exact match.
+use crate::bktree::BkTree;
+use crate::phash::{is_image, phash};
+use std::{
+    collections::HashSet,
+    io,
+    path::{Path, PathBuf},
+};
+
+pub fn find_similar_images(start_dir: &Path, distance: u32, ignore_symlinks: bool) -> io::Result<()> {
+    let mut fingerprints = Vec::new();
+    collect_fingerprints(start_dir, ignore_symlinks, &mut fingerprints)?;
+
+    let mut tree = BkTree::new();
+    for (hash, path) in &fingerprints {
+        tree.insert(*hash, path.clone());
+    }
+
+    let mut reported: HashSet<PathBuf> = HashSet::new();
+    for (hash, path) in &fingerprints {
+        if reported.contains(path) {
+            continue;
+        }
+        let cluster: Vec<PathBuf> = tree
+            .query(*hash, distance)
+            .into_iter()
+            .flat_map(|(_, paths)| paths.to_vec())
+            .collect();
+        if cluster.len() < 2 {
+            continue;
+        }
+        println!("visually similar:");
+        for member in &cluster {
+            println!("  {}", member.display());
+            reported.insert(member.clone());
+        }
+    }
+    Ok(())
+}
+
+fn collect_fingerprints(
+    path: &Path,
+    ignore_symlinks: bool,
+    out: &mut Vec<(u64, PathBuf)>,
+) -> io::Result<()> {
+    if path.is_dir() {
+        for entry in std::fs::read_dir(path)? {
+            let entry_path = entry?.path();
+            if ignore_symlinks && entry_path.is_symlink() {
+                continue;
+            }
+            if entry_path.is_dir() {
+                collect_fingerprints(&entry_path, ignore_symlinks, out)?;
+            } else if is_image(&entry_path) {
+                if let Some(hash) = phash(&entry_path) {
+                    out.push((hash, entry_path));
+                }
+            }
+        }
+    }
+    Ok(())
+}
@@ -1,13 +1,53 @@
+mod actions;
+#[cfg(feature = "similar-images")]
+mod bktree;
+mod cache;
 mod dupe_scanner;
+mod filters;
+#[cfg(feature = "similar-images")]
+mod image_similarity;
+mod output;
+#[cfg(feature = "similar-images")]
+mod phash;
+#[cfg(test)]
+mod test_support;
+use actions::Action;
+use cache::HashCache;
 use clap::Parser;
-use dupe_scanner::DupeScanner;
-use std::path::Path;
+use dupe_scanner::{DupeScanner, HashAlgo};
+use filters::ScanFilters;
+use output::OutputFormat;
+use std::path::{Path, PathBuf};
 
 fn main() -> std::io::Result<()> {
     let args = Args::parse();
     let start_dir = Path::new(&args.directory);
-    let mut dupe_scanner = DupeScanner::from_path(start_dir, args.ignore_symlinks);
+    let cache_path = (!args.no_cache)
+        .then(|| args.cache_path.clone().unwrap_or_else(HashCache::default_path));
+    let filters = ScanFilters::new(
+        args.include_ext.clone(),
+        args.exclude_ext.clone(),
+        args.exclude_path.clone(),
+        args.min_size,
+        args.max_size,
+    );
+    let mut dupe_scanner = DupeScanner::from_path(
+        start_dir,
+        args.ignore_symlinks,
+        args.algorithm,
+        cache_path,
+        args.action,
+        args.dry_run,
+        args.output,
+        filters,
+    );
     dupe_scanner.find_dupes()?;
+
+    #[cfg(feature = "similar-images")]
+    if args.similar_images {
+        image_similarity::find_similar_images(start_dir, args.distance, args.ignore_symlinks)?;
+    }
+
     Ok(())
 }
 
@@ -22,4 +62,59 @@ struct Args {
     /// Ignore Symlinks
     #[arg(short, long)]
     ignore_symlinks: bool,
+
+    /// Hashing algorithm used to fingerprint file contents
+    #[arg(long, default_value = "blake3")]
+    algorithm: HashAlgo,
+
+    /// Disable the persistent hash cache
+    #[arg(long)]
+    no_cache: bool,
+
+    /// Override the hash cache file location
+    #[arg(long)]
+    cache_path: Option<PathBuf>,
+
+    /// What to do with confirmed duplicates
+    #[arg(long, default_value = "report")]
+    action: Action,
+
+    /// Print what `--action` would do without touching the filesystem
+    #[arg(long)]
+    dry_run: bool,
+
+    /// How to render duplicate groups
+    #[arg(long, default_value = "text")]
+    output: OutputFormat,
+
+    /// Only scan files with one of these extensions (e.g. "mp4")
+    #[arg(long)]
+    include_ext: Vec<String>,
+
+    /// Skip files with one of these extensions
+    #[arg(long)]
+    exclude_ext: Vec<String>,
+
+    /// Skip paths matching this glob, e.g. "**/node_modules/**"
+    #[arg(long)]
+    exclude_path: Vec<String>,
+
+    /// Skip files smaller than this many bytes
+    #[arg(long)]
+    min_size: Option<u64>,
+
+    /// Skip files larger than this many bytes
+    #[arg(long)]
+    max_size: Option<u64>,
+
+    /// Also cluster images by perceptual similarity (requires the
+    /// `similar-images` feature)
+    #[cfg(feature = "similar-images")]
+    #[arg(long)]
+    similar_images: bool,
+
+    /// Maximum Hamming distance between perceptual hashes to count as similar
+    #[cfg(feature = "similar-images")]
+    #[arg(long, default_value_t = 5)]
+    distance: u32,
 }
@@ -0,0 +1,169 @@
+use clap::ValueEnum;
+use serde::Serialize;
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+/// How to render the duplicate groups found by a scan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// One `dup = keeper` line per duplicate, the original behavior.
+    Text,
+    /// An object with `total_wasted_bytes` and a `groups` array of
+    /// `{ hash, size, wasted_bytes, paths }` objects.
+    Json,
+    /// One row per file, tagged with its group id.
+    Csv,
+}
+
+#[derive(Debug, Serialize)]
+struct DuplicateGroup {
+    hash: String,
+    size: u64,
+    wasted_bytes: u64,
+    paths: Vec<PathBuf>,
+}
+
+/// The JSON shape: the groups plus the aggregate `wasted_bytes` across all of
+/// them, so a consumer doesn't have to re-sum the per-group field themselves.
+#[derive(Debug, Serialize)]
+struct Report<'a> {
+    total_wasted_bytes: u64,
+    groups: &'a [DuplicateGroup],
+}
+
+/// Renders `groups` (hash -> keeper-first path list, each with at least two
+/// entries) in `format`.
+pub fn report(groups: Vec<(Vec<u8>, Vec<PathBuf>)>, format: OutputFormat) -> io::Result<()> {
+    let groups: Vec<DuplicateGroup> = groups
+        .into_iter()
+        .map(|(hash, paths)| {
+            let size = std::fs::metadata(&paths[0]).map(|m| m.len()).unwrap_or(0);
+            DuplicateGroup {
+                hash: to_hex(&hash),
+                size,
+                wasted_bytes: size * (paths.len() as u64 - 1),
+                paths,
+            }
+        })
+        .collect();
+    let total_wasted: u64 = groups.iter().map(|g| g.wasted_bytes).sum();
+
+    match format {
+        OutputFormat::Text => print_text(&groups, total_wasted),
+        OutputFormat::Json => print_json(&groups, total_wasted)?,
+        OutputFormat::Csv => print_csv(&groups, total_wasted),
+    }
+    Ok(())
+}
+
+fn print_text(groups: &[DuplicateGroup], total_wasted: u64) {
+    for group in groups {
+        let keeper = &group.paths[0];
+        for dup in &group.paths[1..] {
+            println!("{} = {}", dup.display(), keeper.display());
+        }
+    }
+    println!("wasted space: {}", humanize_bytes(total_wasted));
+}
+
+fn print_json(groups: &[DuplicateGroup], total_wasted: u64) -> io::Result<()> {
+    let report = Report {
+        total_wasted_bytes: total_wasted,
+        groups,
+    };
+    let json = serde_json::to_string_pretty(&report)?;
+    println!("{json}");
+    Ok(())
+}
+
+fn print_csv(groups: &[DuplicateGroup], total_wasted: u64) {
+    println!("group_id,path,hash,size");
+    for (id, group) in groups.iter().enumerate() {
+        for path in &group.paths {
+            println!(
+                "{id},{},{},{}",
+                csv_escape(path),
+                group.hash,
+                group.size
+            );
+        }
+    }
+    // Not a data row: a `#`-prefixed trailing line is the common CSV
+    // convention for an aggregate footer (e.g. pandas' `comment='#'`), so
+    // tools that only want the per-file rows can ignore it.
+    println!("# wasted_bytes,{total_wasted}");
+}
+
+fn csv_escape(path: &Path) -> String {
+    let raw = path.to_string_lossy();
+    if raw.contains([',', '"', '\n']) {
+        format!("\"{}\"", raw.replace('"', "\"\""))
+    } else {
+        raw.into_owned()
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Formats a byte count as a human-readable size, e.g. "1.4 GiB".
+fn humanize_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 6] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} {}", UNITS[unit])
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn humanize_bytes_picks_the_right_unit() {
+        assert_eq!(humanize_bytes(512), "512 B");
+        assert_eq!(humanize_bytes(1024), "1.0 KiB");
+        assert_eq!(humanize_bytes(1_500_000_000), "1.4 GiB");
+    }
+
+    #[test]
+    fn to_hex_matches_known_digest() {
+        assert_eq!(to_hex(&[0xde, 0xad, 0xbe, 0xef]), "deadbeef");
+    }
+
+    #[test]
+    fn json_report_includes_total_wasted_bytes() {
+        let groups = vec![DuplicateGroup {
+            hash: "abcd".into(),
+            size: 10,
+            wasted_bytes: 10,
+            paths: vec![PathBuf::from("a"), PathBuf::from("b")],
+        }];
+        let report = Report {
+            total_wasted_bytes: 10,
+            groups: &groups,
+        };
+        let json = serde_json::to_string(&report).unwrap();
+        assert!(json.contains("\"total_wasted_bytes\":10"));
+        assert!(json.contains("\"wasted_bytes\":10"));
+    }
+
+    #[test]
+    fn csv_escape_quotes_only_when_needed() {
+        assert_eq!(csv_escape(Path::new("plain.txt")), "plain.txt");
+        assert_eq!(
+            csv_escape(Path::new("has,comma.txt")),
+            "\"has,comma.txt\""
+        );
+    }
+}
@@ -0,0 +1,90 @@
+//! Perceptual hashing for near-duplicate image detection.
+use image::imageops::FilterType;
+use std::path::Path;
+
+const IMAGE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "gif", "bmp", "webp", "tiff"];
+
+pub fn is_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| IMAGE_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+/// Computes a 64-bit difference hash: downscale to a 9x8 grayscale
+/// thumbnail and set one bit per pixel for whether it's brighter than its
+/// right neighbor. Near-identical images (re-encoded, resized, recompressed)
+/// produce fingerprints a small Hamming distance apart.
+pub fn phash(path: &Path) -> Option<u64> {
+    let thumbnail = image::open(path)
+        .ok()?
+        .resize_exact(9, 8, FilterType::Lanczos3)
+        .into_luma8();
+
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            let left = thumbnail.get_pixel(x, y)[0];
+            let right = thumbnail.get_pixel(x + 1, y)[0];
+            if left > right {
+                hash |= 1 << (y * 8 + x);
+            }
+        }
+    }
+    Some(hash)
+}
+
+pub fn hamming_distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_support::scratch_dir;
+    use image::{Rgb, RgbImage};
+
+    #[test]
+    fn is_image_matches_known_extensions_case_insensitively() {
+        assert!(is_image(Path::new("photo.JPG")));
+        assert!(is_image(Path::new("photo.png")));
+        assert!(!is_image(Path::new("photo.txt")));
+        assert!(!is_image(Path::new("no_extension")));
+    }
+
+    #[test]
+    fn hamming_distance_counts_differing_bits() {
+        assert_eq!(hamming_distance(0b1010, 0b1010), 0);
+        assert_eq!(hamming_distance(0b1010, 0b0010), 1);
+        assert_eq!(hamming_distance(0, u64::MAX), 64);
+    }
+
+    #[test]
+    fn phash_is_stable_and_distinguishes_different_images() {
+        let dir = scratch_dir("phash", "gradient");
+
+        let left_half_dark = gradient_image(true);
+        let left_half_dark_path = dir.join("left_dark.png");
+        left_half_dark.save(&left_half_dark_path).unwrap();
+
+        let right_half_dark = gradient_image(false);
+        let right_half_dark_path = dir.join("right_dark.png");
+        right_half_dark.save(&right_half_dark_path).unwrap();
+
+        let hash_a = phash(&left_half_dark_path).unwrap();
+        let hash_a_again = phash(&left_half_dark_path).unwrap();
+        let hash_b = phash(&right_half_dark_path).unwrap();
+
+        assert_eq!(hash_a, hash_a_again);
+        assert_ne!(hash_a, hash_b);
+    }
+
+    fn gradient_image(dark_on_left: bool) -> RgbImage {
+        let mut img = RgbImage::new(16, 16);
+        for (x, _y, pixel) in img.enumerate_pixels_mut() {
+            let on_dark_side = x < 8;
+            let value = if on_dark_side == dark_on_left { 20 } else { 235 };
+            *pixel = Rgb([value, value, value]);
+        }
+        img
+    }
+}
@@ -0,0 +1,17 @@
+//! Shared fixtures for `#[cfg(test)]` blocks across the crate, so modules
+//! that need a scratch directory on disk don't each paste their own
+//! "temp dir keyed by pid" helper.
+#![cfg(test)]
+
+use std::path::PathBuf;
+
+/// A fresh scratch directory under the OS temp dir, namespaced by `module`
+/// and `label` (plus the process id, so concurrent test runs don't collide).
+pub(crate) fn scratch_dir(module: &str, label: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "dupes-{module}-test-{label}-{}",
+        std::process::id()
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}